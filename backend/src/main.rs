@@ -12,30 +12,73 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod store;
+
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    convert::Infallible,
     env,
-    sync::{Arc, LazyLock, Mutex},
+    sync::{Arc, LazyLock},
 };
 
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Response},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
     routing::{get, post},
 };
+use r2d2_sqlite::SqliteConnectionManager;
 use regex::Regex;
-use rusqlite::{Connection, fallible_iterator::FallibleIterator, params};
+use serde::{Deserialize, Serialize};
+use store::{PostgresStore, SqliteStore, Store, StoreError};
 use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt as _;
+use tokio_stream::wrappers::BroadcastStream;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::{Level, info};
 
+/// How long a SQLite connection will wait on the write lock before giving up.
+/// Generous because WAL mode means readers never contend with the writer.
+const BUSY_TIMEOUT_MS: u32 = 5000;
+
+/// Capacity of the in-memory change channel. Slow SSE subscribers that fall
+/// this far behind miss events rather than back-pressuring writers.
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// Broadcast to every open `/events` stream whenever a command commits a row.
+#[derive(Debug, Clone, Serialize)]
+struct Change {
+    kind: ChangeKind,
+    date: Option<String>,
+    nutrient: String,
+    new_count: i32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ChangeKind {
+    Consume,
+    Unconsume,
+    IncGoal,
+    DecGoal,
+    Batch,
+}
+
+#[derive(Clone)]
+struct AppState {
+    store: Arc<dyn Store>,
+    changes: broadcast::Sender<Change>,
+}
+
 #[derive(Debug, Error)]
 enum AppError {
-    #[cfg_attr(debug_assertions, error("database error: {0}"))]
-    #[cfg_attr(not(debug_assertions), error("database error"))]
-    DatabaseError(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
     #[error("invalid request: {0}")]
     InvalidRequest(&'static str),
 }
@@ -45,7 +88,8 @@ impl IntoResponse for AppError {
         (
             match self {
                 Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
-                _ => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::Store(StoreError::WouldGoNegative) => StatusCode::BAD_REQUEST,
+                Self::Store(StoreError::Backend(_)) => StatusCode::INTERNAL_SERVER_ERROR,
             },
             format!("Something went wrong: {}", self),
         )
@@ -54,26 +98,14 @@ impl IntoResponse for AppError {
 }
 
 async fn get_portions_for_date(
-    conn: State<Arc<Mutex<Connection>>>,
+    state: State<AppState>,
     date: Path<String>,
 ) -> Result<Json<HashMap<String, i32>>, AppError> {
-    let conn = conn.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT name, SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? GROUP BY name",
-    )?;
-    let rows = stmt.query([date.0])?;
-    Ok(Json(rows.map(|r| Ok((r.get(0)?, r.get(1)?))).collect()?))
+    Ok(Json(state.store.get_portions_for_date(&date.0).await?))
 }
 
-async fn get_goals(
-    conn: State<Arc<Mutex<Connection>>>,
-) -> Result<Json<HashMap<String, i32>>, AppError> {
-    let conn = conn.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT nutrient, SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events GROUP BY nutrient",
-    )?;
-    let rows = stmt.query([])?;
-    Ok(Json(rows.map(|r| Ok((r.get(0)?, r.get(1)?))).collect()?))
+async fn get_goals(state: State<AppState>) -> Result<Json<HashMap<String, i32>>, AppError> {
+    Ok(Json(state.store.get_goals().await?))
 }
 
 fn is_valid_date(date: &str) -> bool {
@@ -88,7 +120,7 @@ fn is_valid_nutrient(nutrient: &str) -> bool {
 }
 
 async fn consume_portion(
-    conn: State<Arc<Mutex<Connection>>>,
+    state: State<AppState>,
     Path((date, nutrient)): Path<(String, String)>,
 ) -> Result<Json<&'static str>, AppError> {
     if !is_valid_date(&date) {
@@ -98,18 +130,20 @@ async fn consume_portion(
         return Err(AppError::InvalidRequest("invalid nutrient"));
     }
 
-    let conn = conn.lock().unwrap();
+    let new_count = state.store.consume(&date, &nutrient).await?;
 
-    conn.execute(
-        "INSERT INTO nutrient_events(name, date, type) VALUES (?, ?, 'consume')",
-        [&nutrient, &date],
-    )?;
+    let _ = state.changes.send(Change {
+        kind: ChangeKind::Consume,
+        date: Some(date),
+        nutrient,
+        new_count,
+    });
 
     Ok(Json("success"))
 }
 
 async fn unconsume_portion(
-    conn: State<Arc<Mutex<Connection>>>,
+    state: State<AppState>,
     Path((date, nutrient)): Path<(String, String)>,
 ) -> Result<Json<&'static str>, AppError> {
     if !is_valid_date(&date) {
@@ -119,78 +153,177 @@ async fn unconsume_portion(
         return Err(AppError::InvalidRequest("invalid nutrient"));
     }
 
-    let conn = conn.lock().unwrap();
-
-    // It's safe to do the check and update without transaction because mutex enforces no parallelism
-    let count: Option<i32> = conn.query_row(
-        "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? AND name = ?",
-        [&date, &nutrient],
-        |r| r.get(0))?;
-    if count.is_none_or(|x| x == 0) {
-        return Err(AppError::InvalidRequest(
-            "can't unconsume because the count is already 0",
-        ));
-    }
+    let new_count = state.store.unconsume(&date, &nutrient).await?;
 
-    conn.execute(
-        "INSERT INTO nutrient_events(name, date, type) VALUES (?, ?, 'unconsume')",
-        [&nutrient, &date],
-    )?;
+    let _ = state.changes.send(Change {
+        kind: ChangeKind::Unconsume,
+        date: Some(date),
+        nutrient,
+        new_count,
+    });
 
     Ok(Json("success"))
 }
 
 async fn inc_goal(
     nutrient: Path<String>,
-    conn: State<Arc<Mutex<Connection>>>,
+    state: State<AppState>,
 ) -> Result<Json<&'static str>, AppError> {
     if !is_valid_nutrient(&nutrient) {
         return Err(AppError::InvalidRequest("invalid nutrient"));
     }
 
-    let conn = conn.lock().unwrap();
+    let new_count = state.store.inc_goal(&nutrient).await?;
+
+    let _ = state.changes.send(Change {
+        kind: ChangeKind::IncGoal,
+        date: None,
+        nutrient: nutrient.0,
+        new_count,
+    });
 
-    conn.execute(
-        "INSERT INTO goal_events (nutrient, type) VALUES (?, ?)",
-        params![&*nutrient, "inc"],
-    )?;
     Ok(Json("success"))
 }
 
 async fn dec_goal(
     nutrient: Path<String>,
-    conn: State<Arc<Mutex<Connection>>>,
+    state: State<AppState>,
 ) -> Result<Json<&'static str>, AppError> {
     if !is_valid_nutrient(&nutrient) {
         return Err(AppError::InvalidRequest("invalid nutrient"));
     }
 
-    let conn = conn.lock().unwrap();
-
-    // It's safe to do the check and update without transaction because mutex enforces no parallelism
-    let count: Option<i32> = conn.query_row(
-        "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = ?",
-        [&*nutrient],
-        |r| r.get(0),
-    )?;
-    if count.is_none_or(|x| x == 0) {
-        return Err(AppError::InvalidRequest(
-            "can't decrease because the goal is already 0",
-        ));
+    let new_count = state.store.dec_goal(&nutrient).await?;
+
+    let _ = state.changes.send(Change {
+        kind: ChangeKind::DecGoal,
+        date: None,
+        nutrient: nutrient.0,
+        new_count,
+    });
+
+    Ok(Json("success"))
+}
+
+/// Applies a whole day's log in one request: every op is validated up front
+/// and all inserts commit inside a single transaction, so the batch is
+/// all-or-nothing instead of one HTTP round-trip per portion.
+async fn batch_portions(
+    state: State<AppState>,
+    date: Path<String>,
+    Json(ops): Json<Vec<store::BatchOp>>,
+) -> Result<Json<HashMap<String, i32>>, AppError> {
+    if !is_valid_date(&date) {
+        return Err(AppError::InvalidRequest("invalid date"));
+    }
+    for op in &ops {
+        if !is_valid_nutrient(&op.nutrient) {
+            return Err(AppError::InvalidRequest("invalid nutrient"));
+        }
+    }
+
+    let counts = state.store.apply_batch(&date, &ops).await?;
+
+    let mut notified = HashSet::new();
+    for op in &ops {
+        if notified.insert(op.nutrient.clone()) {
+            if let Some(&new_count) = counts.get(&op.nutrient) {
+                let _ = state.changes.send(Change {
+                    kind: ChangeKind::Batch,
+                    date: Some(date.clone()),
+                    nutrient: op.nutrient.clone(),
+                    new_count,
+                });
+            }
+        }
+    }
+
+    Ok(Json(counts))
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    since_nutrient_events: Option<i64>,
+    since_goal_events: Option<i64>,
+}
+
+/// `GET /events` serves two purposes depending on the query string:
+/// - neither cursor set: stream every committed `Change` as a server-sent
+///   event, so clients can keep their displayed counts in sync without
+///   re-polling `/days/{date}/portions`.
+/// - `?since_nutrient_events={id}&since_goal_events={id}`: return the raw
+///   event log rows past those cursors, so an offline client can catch up
+///   and replay what it missed. The two are separate cursors, not one,
+///   because `nutrient_events` and `goal_events` each have their own `id`
+///   sequence — see `Store::events_since`.
+async fn events_handler(
+    state: State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Response, AppError> {
+    if query.since_nutrient_events.is_some() || query.since_goal_events.is_some() {
+        let events = state
+            .store
+            .events_since(
+                query.since_nutrient_events.unwrap_or(0),
+                query.since_goal_events.unwrap_or(0),
+            )
+            .await?;
+        return Ok(Json(events).into_response());
+    }
+
+    let rx = state.changes.subscribe();
+    let stream = BroadcastStream::new(rx).filter_map(|change| match change {
+        Ok(change) => Some(Ok::<_, Infallible>(Event::default().json_data(change).unwrap())),
+        Err(_lagged) => None,
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+/// Ingests an externally-generated event (e.g. from a client that was
+/// offline), skipping it if its `client_id` was already applied so a retried
+/// submission can't double-apply.
+async fn ingest_event(
+    state: State<AppState>,
+    Json(event): Json<store::IncomingEvent>,
+) -> Result<Json<&'static str>, AppError> {
+    match &event {
+        store::IncomingEvent::NutrientEvents {
+            name, date, r#type, ..
+        } => {
+            if !is_valid_nutrient(name) {
+                return Err(AppError::InvalidRequest("invalid nutrient"));
+            }
+            if !is_valid_date(date) {
+                return Err(AppError::InvalidRequest("invalid date"));
+            }
+            if r#type != "consume" && r#type != "unconsume" {
+                return Err(AppError::InvalidRequest("invalid type"));
+            }
+        }
+        store::IncomingEvent::GoalEvents { nutrient, r#type, .. } => {
+            if !is_valid_nutrient(nutrient) {
+                return Err(AppError::InvalidRequest("invalid nutrient"));
+            }
+            if r#type != "inc" && r#type != "dec" {
+                return Err(AppError::InvalidRequest("invalid type"));
+            }
+        }
     }
 
-    conn.execute(
-        "INSERT INTO goal_events (nutrient, type) VALUES (?, ?)",
-        params![&*nutrient, "dec"],
-    )?;
+    state.store.ingest_event(event).await?;
     Ok(Json("success"))
 }
 
-fn router(conn: rusqlite::Connection) -> Router {
+fn router(store: Arc<dyn Store>) -> Router {
+    let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+    let state = AppState { store, changes };
+
     Router::new()
         // queries
         .route("/days/{date}/portions", get(get_portions_for_date))
         .route("/goals", get(get_goals))
+        // live updates and event-log sync
+        .route("/events", get(events_handler).post(ingest_event))
         // commands
         .route(
             "/days/{date}/portions/{nutrient}/consume",
@@ -202,37 +335,42 @@ fn router(conn: rusqlite::Connection) -> Router {
         )
         .route("/goals/portions/{nutrient}/inc", post(inc_goal))
         .route("/goals/portions/{nutrient}/dec", post(dec_goal))
+        .route("/days/{date}/portions:batch", post(batch_portions))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
-        .with_state(Arc::new(Mutex::new(conn)))
+        .with_state(state)
 }
 
-fn setup_db(conn: &Connection) -> Result<(), rusqlite::Error> {
-    // Design notes:
-    // - pure event sourcing for sync and simple design
-    // - PRIMARY KEY is id, not timestamp, to save me from battling disambiguation if
-    //   multiple things happen at the same time
-    // Possible future optimisations:
-    // - indices to support the queries better
-    // - materialized views to avoid processing all events
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS nutrient_events (
-            id INTEGER PRIMARY KEY,
-            timestamp INT DEFAULT(unixepoch('subsec') * 1000),
-            name TEXT NOT NULL CHECK (name in ('protein', 'carbs', 'vegetables', 'fats')),
-            date TEXT NOT NULL,
-            type TEXT NOT NULL CHECK (type in ('consume', 'unconsume'))) STRICT",
-        [],
-    )?;
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS goal_events (
-            id INTEGER PRIMARY KEY,
-            timestamp INT DEFAULT(unixepoch('subsec') * 1000),
-            nutrient TEXT NOT NULL CHECK (nutrient in ('protein', 'carbs', 'vegetables', 'fats')),
-            type TEXT NOT NULL CHECK (type in ('inc', 'dec'))) STRICT",
-        [],
-    )?;
-    Ok(())
+/// Builds the store selected by `database_url`: a `postgres://`/`postgresql://`
+/// URL backs onto a shared Postgres instance for multi-device sync, anything
+/// else is treated as a local SQLite file path for zero-config dev.
+async fn build_store(database_url: &str) -> Arc<dyn Store> {
+    if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+        let config = database_url
+            .parse()
+            .expect("Failed to parse PORTIONS_DATABASE_URL");
+        let manager = bb8_postgres::PostgresConnectionManager::new(config, tokio_postgres::NoTls);
+        let pool = bb8::Pool::builder()
+            .build(manager)
+            .await
+            .expect("Failed to create postgres connection pool");
+        let store = PostgresStore::new(pool);
+        store.setup().await.expect("Failed to set up postgres schema");
+        Arc::new(store)
+    } else {
+        // WAL mode lets readers (the aggregation queries) run without
+        // blocking the single writer; busy_timeout absorbs the brief
+        // contention that remains.
+        let manager = SqliteConnectionManager::file(database_url).with_init(|conn| {
+            conn.execute_batch(&format!(
+                "PRAGMA journal_mode=WAL; PRAGMA busy_timeout={BUSY_TIMEOUT_MS};"
+            ))
+        });
+        let pool = r2d2::Pool::new(manager).expect("Failed to create connection pool");
+        let store = SqliteStore::new(pool);
+        store.setup().expect("Failed to set up sqlite schema");
+        Arc::new(store)
+    }
 }
 
 #[tokio::main]
@@ -242,27 +380,41 @@ async fn main() {
         .init();
 
     let bind_address = env::var("PORTIONS_BIND_ADDRESS").unwrap_or("0.0.0.0:3000".into());
+    let database_url = env::var("PORTIONS_DATABASE_URL").unwrap_or("nutrients.db".into());
 
-    let conn = Connection::open("nutrients.db").expect("Failed to open nutrients.db");
-    setup_db(&conn).unwrap();
+    let store = build_store(&database_url).await;
 
     info!("Starting server at {}...", bind_address);
     let listener = tokio::net::TcpListener::bind(bind_address).await.unwrap();
-    axum::serve(listener, router(conn)).await.unwrap();
+    axum::serve(listener, router(store)).await.unwrap();
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use axum::Router;
     use axum_test::TestServer;
-    use rusqlite::Connection;
     use serde_json::json;
 
-    use crate::{router, setup_db};
+    use crate::router;
+    use crate::store::SqliteStore;
+
+    fn test_router() -> Router {
+        // A single pooled connection mirrors a real SQLite file: ":memory:"
+        // opens an unrelated empty database per connection, so the pool is
+        // capped at one to keep every request hitting the same database.
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        let store = SqliteStore::new(pool);
+        store.setup().unwrap();
+        router(Arc::new(store))
+    }
 
     fn test_server() -> TestServer {
-        let conn = Connection::open_in_memory().unwrap();
-        setup_db(&conn).unwrap();
-        TestServer::new(router(conn)).unwrap()
+        TestServer::new(test_router()).unwrap()
     }
 
     mod goals {
@@ -455,4 +607,231 @@ mod tests {
             resp.assert_json(&json!({"protein": 0}));
         }
     }
+
+    mod batch {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_batch_applies_all_ops() {
+            let server = test_server();
+            let resp = server
+                .post("/days/2026-01-01/portions:batch")
+                .json(&json!([
+                    {"nutrient": "protein", "op": "consume", "count": 3},
+                    {"nutrient": "carbs", "op": "consume", "count": 1},
+                ]))
+                .await;
+            resp.assert_status_success();
+            resp.assert_json(&json!({"protein": 3, "carbs": 1}));
+
+            let resp = server.get("/days/2026-01-01/portions").await;
+            resp.assert_json(&json!({"protein": 3, "carbs": 1}));
+        }
+
+        #[tokio::test]
+        async fn test_batch_validation_bad_nutrient() {
+            let server = test_server();
+            server
+                .post("/days/2026-01-01/portions:batch")
+                .json(&json!([{"nutrient": "bad", "op": "consume", "count": 1}]))
+                .await
+                .assert_status_bad_request();
+        }
+
+        #[tokio::test]
+        async fn test_batch_validation_bad_date() {
+            let server = test_server();
+            server
+                .post("/days/not-a-date/portions:batch")
+                .json(&json!([{"nutrient": "protein", "op": "consume", "count": 1}]))
+                .await
+                .assert_status_bad_request();
+        }
+
+        #[tokio::test]
+        async fn test_batch_rejects_whole_batch_if_any_op_would_go_negative() {
+            let server = test_server();
+            server
+                .post("/days/2026-01-01/portions:batch")
+                .json(&json!([{"nutrient": "protein", "op": "consume", "count": 2}]))
+                .await
+                .assert_status_success();
+
+            server
+                .post("/days/2026-01-01/portions:batch")
+                .json(&json!([
+                    {"nutrient": "carbs", "op": "consume", "count": 1},
+                    {"nutrient": "protein", "op": "unconsume", "count": 3},
+                ]))
+                .await
+                .assert_status_bad_request();
+
+            // Nothing from the rejected batch, including the valid "carbs"
+            // entry, should have been applied.
+            let resp = server.get("/days/2026-01-01/portions").await;
+            resp.assert_json(&json!({"protein": 2}));
+        }
+    }
+
+    mod events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_stream_events_connects() {
+            use tower::ServiceExt;
+
+            // The body is an unterminated `KeepAlive` SSE stream, so this
+            // can't go through `TestServer`, which buffers the whole body
+            // before returning — that would hang forever. `oneshot` hands
+            // back the response as soon as the head is ready, and a timeout
+            // guards against a real regression hanging the test run.
+            let request = axum::http::Request::builder()
+                .uri("/events")
+                .body(axum::body::Body::empty())
+                .unwrap();
+            let response = tokio::time::timeout(
+                std::time::Duration::from_secs(5),
+                test_router().oneshot(request),
+            )
+            .await
+            .expect("GET /events did not respond")
+            .unwrap();
+
+            assert_eq!(response.status(), axum::http::StatusCode::OK);
+            assert_eq!(
+                response.headers().get("content-type").unwrap(),
+                "text/event-stream"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_since_returns_events_after_cursor() {
+            let server = test_server();
+            server
+                .post("/days/2026-01-01/portions/protein/consume")
+                .await
+                .assert_status_success();
+            server
+                .post("/goals/portions/carbs/inc")
+                .await
+                .assert_status_success();
+
+            let resp = server
+                .get("/events")
+                .add_query_param("since_nutrient_events", 0)
+                .add_query_param("since_goal_events", 0)
+                .await;
+            resp.assert_status_success();
+            let events: Vec<serde_json::Value> = resp.json();
+            assert_eq!(events.len(), 2);
+            assert!(events.iter().any(|e| e["table"] == "nutrient_events"));
+            assert!(events.iter().any(|e| e["table"] == "goal_events"));
+
+            let resp = server
+                .get("/events")
+                .add_query_param("since_nutrient_events", 1)
+                .add_query_param("since_goal_events", 1)
+                .await;
+            resp.assert_json(&json!([]));
+        }
+
+        /// The two event tables are independent id sequences, so a client
+        /// caught up on one table but behind on the other must still get the
+        /// rows it's missing from that table — a single shared cursor can't
+        /// represent that.
+        #[tokio::test]
+        async fn test_since_tracks_per_table_cursors_independently() {
+            let server = test_server();
+            for _ in 0..3 {
+                server
+                    .post("/days/2026-01-01/portions/protein/consume")
+                    .await
+                    .assert_status_success();
+            }
+            server
+                .post("/goals/portions/carbs/inc")
+                .await
+                .assert_status_success();
+
+            // Fully caught up on nutrient_events (3 rows seen), but hasn't
+            // seen the goal_events row yet.
+            let resp = server
+                .get("/events")
+                .add_query_param("since_nutrient_events", 3)
+                .add_query_param("since_goal_events", 0)
+                .await;
+            resp.assert_status_success();
+            let events: Vec<serde_json::Value> = resp.json();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0]["table"], "goal_events");
+        }
+
+        #[tokio::test]
+        async fn test_ingest_is_idempotent_on_client_id() {
+            let server = test_server();
+            let body = json!({
+                "table": "nutrient_events",
+                "client_id": "client-1",
+                "name": "protein",
+                "date": "2026-01-01",
+                "type": "consume",
+            });
+
+            server.post("/events").json(&body).await.assert_status_success();
+            server.post("/events").json(&body).await.assert_status_success();
+
+            let resp = server.get("/days/2026-01-01/portions").await;
+            resp.assert_json(&json!({"protein": 1}));
+        }
+
+        #[tokio::test]
+        async fn test_ingest_rejects_unconsume_that_would_go_negative() {
+            let server = test_server();
+            server
+                .post("/events")
+                .json(&json!({
+                    "table": "nutrient_events",
+                    "client_id": "client-1",
+                    "name": "protein",
+                    "date": "2026-01-01",
+                    "type": "unconsume",
+                }))
+                .await
+                .assert_status_bad_request();
+
+            let resp = server.get("/days/2026-01-01/portions").await;
+            resp.assert_json(&json!({}));
+        }
+
+        #[tokio::test]
+        async fn test_ingest_validation_bad_nutrient() {
+            let server = test_server();
+            server
+                .post("/events")
+                .json(&json!({
+                    "table": "nutrient_events",
+                    "client_id": "client-1",
+                    "name": "bad",
+                    "date": "2026-01-01",
+                    "type": "consume",
+                }))
+                .await
+                .assert_status_bad_request();
+        }
+
+        #[tokio::test]
+        async fn test_ingest_validation_bad_type() {
+            let server = test_server();
+            server
+                .post("/events")
+                .json(&json!({
+                    "table": "goal_events",
+                    "client_id": "client-1",
+                    "nutrient": "protein",
+                    "type": "not-a-type",
+                }))
+                .await
+                .assert_status_bad_request();
+        }
+    }
 }
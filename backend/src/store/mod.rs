@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+mod postgres;
+mod sqlite;
+
+pub use postgres::PostgresStore;
+pub use sqlite::SqliteStore;
+
+/// One entry of a `POST /days/{date}/portions:batch` request body.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchOp {
+    pub nutrient: String,
+    pub op: BatchOpKind,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOpKind {
+    Consume,
+    Unconsume,
+}
+
+/// A single row read back off the append-only log by `GET /events`. `id` is
+/// monotonic and gap-free only *within* `table`'s own sequence — clients must
+/// track a cursor per table, not a single highest id across both.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub timestamp: i64,
+    pub table: &'static str,
+    pub payload: serde_json::Value,
+}
+
+/// An externally-generated event submitted via `POST /events`. `client_id` is
+/// the client-assigned idempotency key: replaying the same event on retry is
+/// a no-op rather than double-applying it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum IncomingEvent {
+    NutrientEvents {
+        client_id: String,
+        name: String,
+        date: String,
+        r#type: String,
+    },
+    GoalEvents {
+        client_id: String,
+        nutrient: String,
+        r#type: String,
+    },
+}
+
+/// Abstracts the operations the server performs over the event-sourced
+/// nutrient/goal state, so a deployment can pick a local SQLite file for
+/// zero-config dev or a shared Postgres instance for multi-device sync
+/// without the handlers caring which one is live.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn get_portions_for_date(&self, date: &str) -> Result<HashMap<String, i32>, StoreError>;
+    async fn get_goals(&self) -> Result<HashMap<String, i32>, StoreError>;
+    /// Records a portion consumed on `date` and returns the resulting count.
+    async fn consume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError>;
+    /// Records a portion un-consumed on `date` and returns the resulting
+    /// count, failing rather than going negative. Implementations must check
+    /// and insert atomically (e.g. a serializing transaction), since nothing
+    /// above this trait serializes concurrent callers.
+    async fn unconsume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError>;
+    /// Increments the goal for `nutrient` and returns the resulting count.
+    async fn inc_goal(&self, nutrient: &str) -> Result<i32, StoreError>;
+    /// Decrements the goal for `nutrient` and returns the resulting count,
+    /// failing rather than going negative. Same atomicity requirement as
+    /// `unconsume`.
+    async fn dec_goal(&self, nutrient: &str) -> Result<i32, StoreError>;
+    /// Applies every op in `ops` to `date` as a single atomic batch — either
+    /// all inserts commit or none do — and returns the resulting per-nutrient
+    /// counts for the day, mirroring `get_portions_for_date`. Implementations
+    /// validate the whole batch against a running per-nutrient count before
+    /// writing anything, so one op that would go negative rejects the batch
+    /// without partially applying it.
+    async fn apply_batch(
+        &self,
+        date: &str,
+        ops: &[BatchOp],
+    ) -> Result<HashMap<String, i32>, StoreError>;
+    /// Every row from either event table past the caller's cursor, for an
+    /// offline client to replay and rebuild its local aggregate.
+    ///
+    /// `nutrient_events` and `goal_events` each have their own `id` sequence,
+    /// so a single scalar cursor can't represent "seen everything up to
+    /// here" across both — a client that only tracked the highest id it had
+    /// seen overall would silently miss rows from whichever table it saw
+    /// fewer ids from. `since_nutrient_events`/`since_goal_events` are the
+    /// per-table cursors the client should persist (the highest `id` it has
+    /// seen *for that table*) and pass back on the next call. For the same
+    /// reason, implementations order the combined result by `timestamp`, not
+    /// `id` — `id` only orders events within their own table.
+    async fn events_since(
+        &self,
+        since_nutrient_events: i64,
+        since_goal_events: i64,
+    ) -> Result<Vec<StoredEvent>, StoreError>;
+    /// Applies an externally-generated event, ignoring it if its `client_id`
+    /// has already been applied so a retried submission can't double-apply.
+    /// An `unconsume`/`dec` event is checked against the current count just
+    /// like `unconsume`/`dec_goal`, so a replayed or out-of-order event can't
+    /// drive the aggregate negative.
+    async fn ingest_event(&self, event: IncomingEvent) -> Result<(), StoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("can't apply this change because the count would go below 0")]
+    WouldGoNegative,
+    #[cfg_attr(debug_assertions, error("database error: {0}"))]
+    #[cfg_attr(not(debug_assertions), error("database error"))]
+    Backend(String),
+}
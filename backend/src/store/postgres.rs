@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bb8_postgres::PostgresConnectionManager;
+use tokio_postgres::NoTls;
+
+use super::{BatchOp, BatchOpKind, IncomingEvent, Store, StoreError, StoredEvent};
+
+// Its own migrations directory, not `SqliteStore`'s: the two backends' SQL
+// dialects diverge (STRICT tables and `unixepoch()` defaults vs BIGSERIAL
+// and TIMESTAMPTZ), so the migration files can't be shared.
+mod migrations {
+    refinery::embed_migrations!("migrations_postgres");
+}
+
+impl From<bb8::RunError<tokio_postgres::Error>> for StoreError {
+    fn from(e: bb8::RunError<tokio_postgres::Error>) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+impl From<tokio_postgres::Error> for StoreError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+impl From<refinery::Error> for StoreError {
+    fn from(e: refinery::Error) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+/// Shared store backed by a Postgres instance, so multiple devices can read
+/// and write the same event log instead of each keeping its own SQLite file.
+/// Built on the async `tokio-postgres` driver and a `bb8` pool, unlike
+/// `SqliteStore`'s sync `rusqlite`: a blocking Postgres round-trip here would
+/// stall the Tokio worker thread it runs on, and with it every other
+/// request sharing that thread — including the SSE streams `/events` holds
+/// open indefinitely.
+pub struct PostgresStore {
+    pool: bb8::Pool<PostgresConnectionManager<NoTls>>,
+}
+
+impl PostgresStore {
+    pub fn new(pool: bb8::Pool<PostgresConnectionManager<NoTls>>) -> Self {
+        Self { pool }
+    }
+
+    /// Brings the database up to the latest schema. Must be called once
+    /// before the store is used.
+    pub async fn setup(&self) -> Result<(), StoreError> {
+        let mut conn = self.pool.get().await?;
+        migrations::migrations::runner().run_async(&mut *conn).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn get_portions_for_date(&self, date: &str) -> Result<HashMap<String, i32>, StoreError> {
+        let conn = self.pool.get().await?;
+        let rows = conn
+            .query(
+                "SELECT name, SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 GROUP BY name",
+                &[&date],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.get::<_, String>(0), r.get::<_, i64>(1) as i32))
+            .collect())
+    }
+
+    async fn get_goals(&self) -> Result<HashMap<String, i32>, StoreError> {
+        let conn = self.pool.get().await?;
+        let rows = conn
+            .query(
+                "SELECT nutrient, SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events GROUP BY nutrient",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.get::<_, String>(0), r.get::<_, i64>(1) as i32))
+            .collect())
+    }
+
+    async fn consume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError> {
+        let conn = self.pool.get().await?;
+        conn.execute(
+            "INSERT INTO nutrient_events(name, date, type) VALUES ($1, $2, 'consume')",
+            &[&nutrient, &date],
+        )
+        .await?;
+        let new_count: i64 = conn
+            .query_one(
+                "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 AND name = $2",
+                &[&date, &nutrient],
+            )
+            .await?
+            .get(0);
+        Ok(new_count as i32)
+    }
+
+    async fn unconsume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        tx.execute("LOCK TABLE nutrient_events IN SHARE ROW EXCLUSIVE MODE", &[])
+            .await?;
+
+        let count: Option<i64> = tx
+            .query_opt(
+                "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 AND name = $2",
+                &[&date, &nutrient],
+            )
+            .await?
+            .and_then(|r| r.get::<_, Option<i64>>(0));
+        if count.is_none_or(|x| x == 0) {
+            return Err(StoreError::WouldGoNegative);
+        }
+
+        tx.execute(
+            "INSERT INTO nutrient_events(name, date, type) VALUES ($1, $2, 'unconsume')",
+            &[&nutrient, &date],
+        )
+        .await?;
+        let new_count = (count.unwrap() - 1) as i32;
+        tx.commit().await?;
+        Ok(new_count)
+    }
+
+    async fn inc_goal(&self, nutrient: &str) -> Result<i32, StoreError> {
+        let conn = self.pool.get().await?;
+        conn.execute(
+            "INSERT INTO goal_events (nutrient, type) VALUES ($1, 'inc')",
+            &[&nutrient],
+        )
+        .await?;
+        let new_count: i64 = conn
+            .query_one(
+                "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = $1",
+                &[&nutrient],
+            )
+            .await?
+            .get(0);
+        Ok(new_count as i32)
+    }
+
+    async fn dec_goal(&self, nutrient: &str) -> Result<i32, StoreError> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        tx.execute("LOCK TABLE goal_events IN SHARE ROW EXCLUSIVE MODE", &[])
+            .await?;
+
+        let count: Option<i64> = tx
+            .query_opt(
+                "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = $1",
+                &[&nutrient],
+            )
+            .await?
+            .and_then(|r| r.get::<_, Option<i64>>(0));
+        if count.is_none_or(|x| x == 0) {
+            return Err(StoreError::WouldGoNegative);
+        }
+
+        tx.execute(
+            "INSERT INTO goal_events (nutrient, type) VALUES ($1, 'dec')",
+            &[&nutrient],
+        )
+        .await?;
+        let new_count = (count.unwrap() - 1) as i32;
+        tx.commit().await?;
+        Ok(new_count)
+    }
+
+    async fn apply_batch(
+        &self,
+        date: &str,
+        ops: &[BatchOp],
+    ) -> Result<HashMap<String, i32>, StoreError> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        tx.execute("LOCK TABLE nutrient_events IN SHARE ROW EXCLUSIVE MODE", &[])
+            .await?;
+
+        let mut running: HashMap<&str, i32> = HashMap::new();
+        for op in ops {
+            if !running.contains_key(op.nutrient.as_str()) {
+                let current: Option<i64> = tx
+                    .query_one(
+                        "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 AND name = $2",
+                        &[&date, &op.nutrient],
+                    )
+                    .await?
+                    .get(0);
+                running.insert(&op.nutrient, current.unwrap_or(0) as i32);
+            }
+            let count = running.get_mut(op.nutrient.as_str()).unwrap();
+            match op.op {
+                BatchOpKind::Consume => *count += op.count as i32,
+                BatchOpKind::Unconsume => {
+                    *count -= op.count as i32;
+                    if *count < 0 {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+            }
+        }
+
+        for op in ops {
+            let event_type = match op.op {
+                BatchOpKind::Consume => "consume",
+                BatchOpKind::Unconsume => "unconsume",
+            };
+            for _ in 0..op.count {
+                tx.execute(
+                    "INSERT INTO nutrient_events(name, date, type) VALUES ($1, $2, $3)",
+                    &[&op.nutrient, &date, &event_type],
+                )
+                .await?;
+            }
+        }
+
+        let rows = tx
+            .query(
+                "SELECT name, SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 GROUP BY name",
+                &[&date],
+            )
+            .await?;
+        let result = rows
+            .into_iter()
+            .map(|r| (r.get::<_, String>(0), r.get::<_, i64>(1) as i32))
+            .collect();
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    async fn events_since(
+        &self,
+        since_nutrient_events: i64,
+        since_goal_events: i64,
+    ) -> Result<Vec<StoredEvent>, StoreError> {
+        let conn = self.pool.get().await?;
+
+        let rows = conn
+            .query(
+                "SELECT id, (EXTRACT(EPOCH FROM ts) * 1000)::BIGINT, name, date, type
+                 FROM nutrient_events WHERE id > $1 ORDER BY id",
+                &[&since_nutrient_events],
+            )
+            .await?;
+        let mut events: Vec<StoredEvent> = rows
+            .into_iter()
+            .map(|r| StoredEvent {
+                id: r.get(0),
+                timestamp: r.get(1),
+                table: "nutrient_events",
+                payload: serde_json::json!({
+                    "name": r.get::<_, String>(2),
+                    "date": r.get::<_, String>(3),
+                    "type": r.get::<_, String>(4),
+                }),
+            })
+            .collect();
+
+        let rows = conn
+            .query(
+                "SELECT id, (EXTRACT(EPOCH FROM ts) * 1000)::BIGINT, nutrient, type
+                 FROM goal_events WHERE id > $1 ORDER BY id",
+                &[&since_goal_events],
+            )
+            .await?;
+        events.extend(rows.into_iter().map(|r| StoredEvent {
+            id: r.get(0),
+            timestamp: r.get(1),
+            table: "goal_events",
+            payload: serde_json::json!({
+                "nutrient": r.get::<_, String>(2),
+                "type": r.get::<_, String>(3),
+            }),
+        }));
+
+        events.sort_by_key(|e| e.timestamp);
+        Ok(events)
+    }
+
+    async fn ingest_event(&self, event: IncomingEvent) -> Result<(), StoreError> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        match event {
+            IncomingEvent::NutrientEvents {
+                client_id,
+                name,
+                date,
+                r#type,
+            } => {
+                tx.execute("LOCK TABLE nutrient_events IN SHARE ROW EXCLUSIVE MODE", &[])
+                    .await?;
+
+                let already_applied: bool = tx
+                    .query_one(
+                        "SELECT EXISTS(SELECT 1 FROM nutrient_events WHERE client_id = $1)",
+                        &[&client_id],
+                    )
+                    .await?
+                    .get(0);
+                if already_applied {
+                    return Ok(());
+                }
+                if r#type == "unconsume" {
+                    let count: Option<i64> = tx
+                        .query_opt(
+                            "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = $1 AND name = $2",
+                            &[&date, &name],
+                        )
+                        .await?
+                        .and_then(|r| r.get::<_, Option<i64>>(0));
+                    if count.is_none_or(|x| x == 0) {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+                tx.execute(
+                    "INSERT INTO nutrient_events (name, date, type, client_id) VALUES ($1, $2, $3, $4)",
+                    &[&name, &date, &r#type, &client_id],
+                )
+                .await?;
+            }
+            IncomingEvent::GoalEvents {
+                client_id,
+                nutrient,
+                r#type,
+            } => {
+                tx.execute("LOCK TABLE goal_events IN SHARE ROW EXCLUSIVE MODE", &[])
+                    .await?;
+
+                let already_applied: bool = tx
+                    .query_one(
+                        "SELECT EXISTS(SELECT 1 FROM goal_events WHERE client_id = $1)",
+                        &[&client_id],
+                    )
+                    .await?
+                    .get(0);
+                if already_applied {
+                    return Ok(());
+                }
+                if r#type == "dec" {
+                    let count: Option<i64> = tx
+                        .query_opt(
+                            "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = $1",
+                            &[&nutrient],
+                        )
+                        .await?
+                        .and_then(|r| r.get::<_, Option<i64>>(0));
+                    if count.is_none_or(|x| x == 0) {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+                tx.execute(
+                    "INSERT INTO goal_events (nutrient, type, client_id) VALUES ($1, $2, $3)",
+                    &[&nutrient, &r#type, &client_id],
+                )
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{TransactionBehavior, fallible_iterator::FallibleIterator, params};
+
+use super::{BatchOp, BatchOpKind, IncomingEvent, Store, StoreError, StoredEvent};
+
+// Design notes:
+// - pure event sourcing for sync and simple design
+// - PRIMARY KEY is id, not timestamp, to save me from battling disambiguation if
+//   multiple things happen at the same time
+mod migrations {
+    refinery::embed_migrations!("migrations");
+}
+
+impl From<r2d2::Error> for StoreError {
+    fn from(e: r2d2::Error) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+impl From<refinery::Error> for StoreError {
+    fn from(e: refinery::Error) -> Self {
+        StoreError::Backend(e.to_string())
+    }
+}
+
+/// Zero-config store backed by a local `rusqlite` file, pooled with r2d2 in
+/// WAL mode. Good fit for a single-device dev setup.
+pub struct SqliteStore {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+impl SqliteStore {
+    pub fn new(pool: Pool<SqliteConnectionManager>) -> Self {
+        Self { pool }
+    }
+
+    /// Brings the database up to the latest schema. Must be called once
+    /// before the store is used.
+    pub fn setup(&self) -> Result<(), StoreError> {
+        let mut conn = self.pool.get()?;
+        migrations::migrations::runner().run(&mut *conn)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn get_portions_for_date(&self, date: &str) -> Result<HashMap<String, i32>, StoreError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT name, SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? GROUP BY name",
+        )?;
+        let rows = stmt.query([date])?;
+        Ok(rows.map(|r| Ok((r.get(0)?, r.get(1)?))).collect()?)
+    }
+
+    async fn get_goals(&self) -> Result<HashMap<String, i32>, StoreError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT nutrient, SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events GROUP BY nutrient",
+        )?;
+        let rows = stmt.query([])?;
+        Ok(rows.map(|r| Ok((r.get(0)?, r.get(1)?))).collect()?)
+    }
+
+    async fn consume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO nutrient_events(name, date, type) VALUES (?, ?, 'consume')",
+            [nutrient, date],
+        )?;
+        let new_count: i32 = conn.query_row(
+            "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? AND name = ?",
+            [date, nutrient],
+            |r| r.get(0),
+        )?;
+        Ok(new_count)
+    }
+
+    async fn unconsume(&self, date: &str, nutrient: &str) -> Result<i32, StoreError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let count: Option<i32> = tx.query_row(
+            "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? AND name = ?",
+            [date, nutrient],
+            |r| r.get(0),
+        )?;
+        if count.is_none_or(|x| x == 0) {
+            return Err(StoreError::WouldGoNegative);
+        }
+
+        tx.execute(
+            "INSERT INTO nutrient_events(name, date, type) VALUES (?, ?, 'unconsume')",
+            [nutrient, date],
+        )?;
+        let new_count = count.unwrap() - 1;
+        tx.commit()?;
+        Ok(new_count)
+    }
+
+    async fn inc_goal(&self, nutrient: &str) -> Result<i32, StoreError> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO goal_events (nutrient, type) VALUES (?, ?)",
+            params![nutrient, "inc"],
+        )?;
+        let new_count: i32 = conn.query_row(
+            "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = ?",
+            [nutrient],
+            |r| r.get(0),
+        )?;
+        Ok(new_count)
+    }
+
+    async fn dec_goal(&self, nutrient: &str) -> Result<i32, StoreError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let count: Option<i32> = tx.query_row(
+            "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = ?",
+            [nutrient],
+            |r| r.get(0),
+        )?;
+        if count.is_none_or(|x| x == 0) {
+            return Err(StoreError::WouldGoNegative);
+        }
+
+        tx.execute(
+            "INSERT INTO goal_events (nutrient, type) VALUES (?, ?)",
+            params![nutrient, "dec"],
+        )?;
+        let new_count = count.unwrap() - 1;
+        tx.commit()?;
+        Ok(new_count)
+    }
+
+    async fn apply_batch(
+        &self,
+        date: &str,
+        ops: &[BatchOp],
+    ) -> Result<HashMap<String, i32>, StoreError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let mut running: HashMap<&str, i32> = HashMap::new();
+        for op in ops {
+            if !running.contains_key(op.nutrient.as_str()) {
+                let current: Option<i32> = tx.query_row(
+                    "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? AND name = ?",
+                    [date, op.nutrient.as_str()],
+                    |r| r.get(0),
+                )?;
+                running.insert(&op.nutrient, current.unwrap_or(0));
+            }
+            let count = running.get_mut(op.nutrient.as_str()).unwrap();
+            match op.op {
+                BatchOpKind::Consume => *count += op.count as i32,
+                BatchOpKind::Unconsume => {
+                    *count -= op.count as i32;
+                    if *count < 0 {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+            }
+        }
+
+        for op in ops {
+            let event_type = match op.op {
+                BatchOpKind::Consume => "consume",
+                BatchOpKind::Unconsume => "unconsume",
+            };
+            for _ in 0..op.count {
+                tx.execute(
+                    "INSERT INTO nutrient_events(name, date, type) VALUES (?, ?, ?)",
+                    [op.nutrient.as_str(), date, event_type],
+                )?;
+            }
+        }
+
+        let mut stmt = tx.prepare(
+            "SELECT name, SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? GROUP BY name",
+        )?;
+        let rows = stmt.query([date])?;
+        let result: HashMap<String, i32> = rows.map(|r| Ok((r.get(0)?, r.get(1)?))).collect()?;
+        drop(stmt);
+        tx.commit()?;
+        Ok(result)
+    }
+
+    async fn events_since(
+        &self,
+        since_nutrient_events: i64,
+        since_goal_events: i64,
+    ) -> Result<Vec<StoredEvent>, StoreError> {
+        let conn = self.pool.get()?;
+        let mut events = Vec::new();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, name, date, type FROM nutrient_events WHERE id > ? ORDER BY id",
+        )?;
+        let rows = stmt.query([since_nutrient_events])?;
+        events.extend(
+            rows.map(|r| {
+                Ok(StoredEvent {
+                    id: r.get(0)?,
+                    timestamp: r.get(1)?,
+                    table: "nutrient_events",
+                    payload: serde_json::json!({
+                        "name": r.get::<_, String>(2)?,
+                        "date": r.get::<_, String>(3)?,
+                        "type": r.get::<_, String>(4)?,
+                    }),
+                })
+            })
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?,
+        );
+        drop(stmt);
+
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, nutrient, type FROM goal_events WHERE id > ? ORDER BY id",
+        )?;
+        let rows = stmt.query([since_goal_events])?;
+        events.extend(
+            rows.map(|r| {
+                Ok(StoredEvent {
+                    id: r.get(0)?,
+                    timestamp: r.get(1)?,
+                    table: "goal_events",
+                    payload: serde_json::json!({
+                        "nutrient": r.get::<_, String>(2)?,
+                        "type": r.get::<_, String>(3)?,
+                    }),
+                })
+            })
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?,
+        );
+
+        events.sort_by_key(|e| e.timestamp);
+        Ok(events)
+    }
+
+    async fn ingest_event(&self, event: IncomingEvent) -> Result<(), StoreError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        match event {
+            IncomingEvent::NutrientEvents {
+                client_id,
+                name,
+                date,
+                r#type,
+            } => {
+                let already_applied: bool = tx.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM nutrient_events WHERE client_id = ?)",
+                    [&client_id],
+                    |r| r.get(0),
+                )?;
+                if already_applied {
+                    return Ok(());
+                }
+                if r#type == "unconsume" {
+                    let count: Option<i32> = tx.query_row(
+                        "SELECT SUM(CASE type WHEN 'consume' THEN 1 ELSE -1 END) FROM nutrient_events WHERE date = ? AND name = ?",
+                        [date.as_str(), name.as_str()],
+                        |r| r.get(0),
+                    )?;
+                    if count.is_none_or(|x| x == 0) {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+                tx.execute(
+                    "INSERT INTO nutrient_events (name, date, type, client_id) VALUES (?, ?, ?, ?)",
+                    params![name, date, r#type, client_id],
+                )?;
+            }
+            IncomingEvent::GoalEvents {
+                client_id,
+                nutrient,
+                r#type,
+            } => {
+                let already_applied: bool = tx.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM goal_events WHERE client_id = ?)",
+                    [&client_id],
+                    |r| r.get(0),
+                )?;
+                if already_applied {
+                    return Ok(());
+                }
+                if r#type == "dec" {
+                    let count: Option<i32> = tx.query_row(
+                        "SELECT SUM(CASE type WHEN 'inc' THEN 1 ELSE -1 END) FROM goal_events WHERE nutrient = ?",
+                        [nutrient.as_str()],
+                        |r| r.get(0),
+                    )?;
+                    if count.is_none_or(|x| x == 0) {
+                        return Err(StoreError::WouldGoNegative);
+                    }
+                }
+                tx.execute(
+                    "INSERT INTO goal_events (nutrient, type, client_id) VALUES (?, ?, ?)",
+                    params![nutrient, r#type, client_id],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}